@@ -1,12 +1,28 @@
 #![no_std]
 //! A crate that allows you to untype your types.
 //!
-//! This provides 2 functions:
-//!
 //! [`type_equal`] allows you to check if two types are the same.
 //!
 //! [`unty`] allows you to downcast a generic type into a concrete type.
 //!
+//! [`type_equal_strict`] and [`unty_strict`] are stricter variants that additionally compare
+//! size, alignment, drop-ness, and type name, making an accidental [`TypeId`] collision
+//! effectively impossible to act on.
+//!
+//! [`unty_ref`] and [`unty_mut`] downcast a reference without taking ownership of the value.
+//!
+//! [`UntyBox`] is an owning, type-erased container for non-`'static` values, for when you need
+//! to store rather than immediately downcast a generic value.
+//!
+//! [`unty_static`] is a safe alternative to [`unty`] for the common case where `Src` and
+//! `Target` are both `'static`.
+//!
+//! Note: there is no lifetime-checked variant of [`unty`] for non-`'static` types. A trait bound
+//! of the form `Target: 'a` cannot express "`Target` does not outlive `'a`", because outlives
+//! bounds are satisfied by subtyping: `&'static str: 'a` holds for every `'a`, so nothing stops
+//! `Target` from unifying with a longer-lived type than `Src` actually borrows from. For
+//! non-`'static` types [`unty`] is therefore the only option, with the caveats described below.
+//!
 //! This is mostly useful for generic functions, e.g.
 //!
 //! ```
@@ -35,8 +51,14 @@
 //! # }
 //! ```
 
+extern crate alloc;
+
 use core::{any::TypeId, marker::PhantomData, mem};
 
+mod untybox;
+
+pub use untybox::UntyBox;
+
 /// Untypes your types. For documentation see the root of this crate.
 ///
 /// # Safety
@@ -51,6 +73,91 @@ pub unsafe fn unty<Src, Target: 'static>(x: Src) -> Result<Target, Src> {
     }
 }
 
+/// Untypes your types, using [`type_equal_strict`] instead of [`type_equal`] to guard the
+/// transmute. For documentation see the root of this crate.
+///
+/// Prefer this over [`unty`] when `Src` and `Target` are `Sized`: the extra fingerprint checks
+/// make an accidental transmute between two genuinely different types effectively impossible, at
+/// no runtime cost once the types are statically known.
+///
+/// # Safety
+///
+/// This should not be used with types with lifetimes.
+pub unsafe fn unty_strict<Src, Target: 'static>(x: Src) -> Result<Target, Src> {
+    if type_equal_strict::<Src, Target>() {
+        let x = mem::ManuallyDrop::new(x);
+        Ok(mem::transmute_copy::<Src, Target>(&x))
+    } else {
+        Err(x)
+    }
+}
+
+/// Untypes a reference to your type, without taking ownership of it. For documentation see the
+/// root of this crate.
+///
+/// This is useful for generic code that only wants to inspect a value, rather than consume it
+/// via [`unty`]: no move, no clone, no drop to worry about.
+///
+/// # Safety
+///
+/// This should not be used with types with lifetimes.
+pub unsafe fn unty_ref<Src: ?Sized, Target: 'static>(x: &Src) -> Option<&Target> {
+    if type_equal::<Src, Target>() {
+        Some(&*(x as *const Src as *const Target))
+    } else {
+        None
+    }
+}
+
+/// Untypes a mutable reference to your type, without taking ownership of it. For documentation
+/// see the root of this crate.
+///
+/// See [`unty_ref`] for the shared-reference variant.
+///
+/// # Safety
+///
+/// This should not be used with types with lifetimes.
+pub unsafe fn unty_mut<Src: ?Sized, Target: 'static>(x: &mut Src) -> Option<&mut Target> {
+    if type_equal::<Src, Target>() {
+        Some(&mut *(x as *mut Src as *mut Target))
+    } else {
+        None
+    }
+}
+
+/// Untypes your types, for the common case where both `Src` and `Target` are `'static`.
+///
+/// Unlike [`unty`], this compares `Src` and `Target` with [`TypeId::of`] directly rather than
+/// [`type_equal`]. Since `'static` types have no lifetime parameters to confuse, this comparison
+/// is exact, so this function is sound to call without `unsafe`, and is immune to the
+/// `&'a str`/`&'static str` false-positive documented in the crate root.
+///
+/// Prefer this over [`unty`] whenever `Src` and `Target` are `'static`.
+///
+/// ```
+/// # use unty::unty_static;
+/// assert_eq!(unty_static::<u8, u8>(1), Ok(1));
+/// assert_eq!(unty_static::<u8, u16>(1), Err(1));
+/// ```
+///
+/// Because both type parameters are bounded on `'static`, the false positive from the crate
+/// root's example does not compile here:
+///
+/// ```compile_fail
+/// # use unty::unty_static;
+/// fn foo<'a>(input: &'a str) -> Result<&'static str, &'a str> {
+///     unty_static::<&'a str, &'static str>(input)
+/// }
+/// ```
+pub fn unty_static<Src: 'static, Target: 'static>(x: Src) -> Result<Target, Src> {
+    if TypeId::of::<Src>() == TypeId::of::<Target>() {
+        let x = mem::ManuallyDrop::new(x);
+        Ok(unsafe { mem::transmute_copy::<Src, Target>(&x) })
+    } else {
+        Err(x)
+    }
+}
+
 /// Checks to see if the two types are equal.
 ///
 /// ```
@@ -79,9 +186,35 @@ pub fn type_equal<Src: ?Sized, Target: ?Sized>() -> bool {
     non_static_type_id::<Src>() == non_static_type_id::<Target>()
 }
 
+/// Checks to see if the two types are equal, using extra fingerprinting on top of the
+/// [`TypeId`] comparison done by [`type_equal`].
+///
+/// [`TypeId`] is a 64/128-bit hash, so it is not guaranteed to be collision-free. This function
+/// additionally compares `size_of`, `align_of`, `needs_drop`, and `type_name` for `Src` and
+/// `Target`, all of which are compile-time constants the optimizer folds away when the types are
+/// statically known. A hash collision would have to also agree on all of these to slip past this
+/// check, which is astronomically unlikely in practice.
+///
+/// Unlike [`type_equal`], this is bounded on `Sized` because `size_of`/`align_of` are not
+/// available for unsized types. Callers who need to compare unsized types must keep using
+/// [`type_equal`].
+///
+/// ```
+/// # use unty::type_equal_strict;
+/// assert!(type_equal_strict::<u8, u8>());
+/// assert!(!type_equal_strict::<u8, u16>());
+/// ```
+pub fn type_equal_strict<Src, Target>() -> bool {
+    type_equal::<Src, Target>()
+        && mem::size_of::<Src>() == mem::size_of::<Target>()
+        && mem::align_of::<Src>() == mem::align_of::<Target>()
+        && mem::needs_drop::<Src>() == mem::needs_drop::<Target>()
+        && core::any::type_name::<Src>() == core::any::type_name::<Target>()
+}
+
 // Code by dtolnay in a bincode issue:
 // https://github.com/bincode-org/bincode/issues/665#issue-1903241159
-fn non_static_type_id<T: ?Sized>() -> TypeId {
+pub(crate) fn non_static_type_id<T: ?Sized>() -> TypeId {
     trait NonStaticAny {
         fn get_type_id(&self) -> TypeId
         where
@@ -123,3 +256,30 @@ fn test_double_drop() {
     foo(Ty);
     assert_eq!(COUNTER.load(Ordering::Relaxed), 1);
 }
+
+#[test]
+fn test_type_equal_strict() {
+    assert!(type_equal_strict::<u8, u8>());
+    assert!(!type_equal_strict::<u8, u16>());
+    assert!(!type_equal_strict::<u32, i32>());
+
+    assert_eq!(unsafe { unty_strict::<u8, u8>(1) }, Ok(1));
+    assert_eq!(unsafe { unty_strict::<u8, u16>(1) }, Err(1));
+}
+
+#[test]
+fn test_unty_ref_mut() {
+    let mut x = 10u8;
+
+    assert_eq!(unsafe { unty_ref::<u8, u16>(&x) }, None);
+    assert_eq!(unsafe { unty_ref::<u8, u8>(&x) }, Some(&10));
+
+    *unsafe { unty_mut::<u8, u8>(&mut x) }.unwrap() = 20;
+    assert_eq!(x, 20);
+}
+
+#[test]
+fn test_unty_static() {
+    assert_eq!(unty_static::<u8, u8>(1), Ok(1));
+    assert_eq!(unty_static::<u8, u16>(1), Err(1));
+}
@@ -0,0 +1,152 @@
+//! Type-erased, owning container for values that need not be `'static`.
+//!
+//! See [`UntyBox`] for details.
+
+use core::any::TypeId;
+use core::mem::{self, MaybeUninit};
+use core::ptr;
+
+use alloc::boxed::Box;
+
+use crate::non_static_type_id;
+
+/// Number of `usize` words stored inline before [`UntyBox`] falls back to heap-allocating.
+const INLINE_WORDS: usize = 3;
+
+union Value {
+    ptr: *mut (),
+    inline: [MaybeUninit<usize>; INLINE_WORDS],
+}
+
+fn fits_inline<T>() -> bool {
+    mem::size_of::<T>() <= mem::size_of::<Value>() && mem::align_of::<T>() <= mem::align_of::<Value>()
+}
+
+unsafe fn drop_impl<T>(value: &mut Value) {
+    if fits_inline::<T>() {
+        ptr::drop_in_place(value.inline.as_mut_ptr() as *mut T);
+    } else {
+        drop(Box::from_raw(value.ptr as *mut T));
+    }
+}
+
+/// A type-erased container that owns a single value of any type, including non-`'static` ones
+/// that [`core::any::Any`] cannot express.
+///
+/// Values small enough to fit in [`UntyBox`]'s own inline storage are kept there; larger values
+/// are boxed. Either way [`UntyBox`] remembers how to drop its contents correctly, and how to
+/// hand the value back out through [`downcast`](UntyBox::downcast) or
+/// [`downcast_ref`](UntyBox::downcast_ref).
+///
+/// Like [`unty`](crate::unty), [`UntyBox`] identifies its contents via `non_static_type_id`,
+/// so it is unsound to mix types that only differ by lifetime (see the crate root for details).
+pub struct UntyBox {
+    value: Value,
+    type_id: TypeId,
+    drop: unsafe fn(&mut Value),
+}
+
+impl UntyBox {
+    /// Stores `value` in a new [`UntyBox`], boxing it if it does not fit inline.
+    ///
+    /// # Safety
+    ///
+    /// This should not be used with types with lifetimes, as `T` is identified by
+    /// `non_static_type_id` rather than [`TypeId`] and cannot distinguish `&'a T` from
+    /// `&'b T`.
+    pub unsafe fn new<T>(value: T) -> UntyBox {
+        let value = if fits_inline::<T>() {
+            let mut inline = [MaybeUninit::uninit(); INLINE_WORDS];
+            ptr::write(inline.as_mut_ptr() as *mut T, value);
+            Value { inline }
+        } else {
+            Value {
+                ptr: Box::into_raw(Box::new(value)) as *mut (),
+            }
+        };
+
+        UntyBox {
+            value,
+            type_id: non_static_type_id::<T>(),
+            drop: drop_impl::<T>,
+        }
+    }
+
+    /// Takes the value back out of the box if it is a `T`, or hands the box back unchanged if
+    /// it is not.
+    ///
+    /// # Safety
+    ///
+    /// This should not be used with types with lifetimes. See [`UntyBox::new`].
+    pub unsafe fn downcast<T>(self) -> Result<T, UntyBox> {
+        if self.type_id == non_static_type_id::<T>() {
+            let value = if fits_inline::<T>() {
+                ptr::read(self.value.inline.as_ptr() as *const T)
+            } else {
+                *Box::from_raw(self.value.ptr as *mut T)
+            };
+            mem::forget(self);
+            Ok(value)
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Borrows the value out of the box if it is a `T`.
+    ///
+    /// # Safety
+    ///
+    /// This should not be used with types with lifetimes. See [`UntyBox::new`].
+    pub unsafe fn downcast_ref<T>(&self) -> Option<&T> {
+        if self.type_id == non_static_type_id::<T>() {
+            Some(if fits_inline::<T>() {
+                &*(self.value.inline.as_ptr() as *const T)
+            } else {
+                &*(self.value.ptr as *const T)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for UntyBox {
+    fn drop(&mut self) {
+        unsafe { (self.drop)(&mut self.value) }
+    }
+}
+
+#[test]
+fn test_untybox_inline() {
+    let b = unsafe { UntyBox::new(10u8) };
+    assert_eq!(unsafe { b.downcast_ref::<u16>() }, None);
+    assert_eq!(unsafe { b.downcast_ref::<u8>() }, Some(&10));
+    assert_eq!(unsafe { b.downcast::<u8>() }.ok(), Some(10));
+}
+
+#[test]
+fn test_untybox_boxed() {
+    #[derive(Debug, PartialEq)]
+    struct Big([usize; 8]);
+
+    let b = unsafe { UntyBox::new(Big([1; 8])) };
+    assert_eq!(unsafe { b.downcast::<Big>() }.ok(), Some(Big([1; 8])));
+}
+
+#[test]
+fn test_untybox_drop() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    struct Ty;
+
+    impl Drop for Ty {
+        fn drop(&mut self) {
+            COUNTER.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let b = unsafe { UntyBox::new(Ty) };
+    drop(b);
+    assert_eq!(COUNTER.load(Ordering::Relaxed), 1);
+}